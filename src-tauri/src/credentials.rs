@@ -0,0 +1,237 @@
+//! Pluggable storage for auth token secrets.
+//!
+//! Tokens are normally kept in the OS keychain, but some environments (headless
+//! Linux without a Secret Service daemon, users who already run 1Password/pass/
+//! Vault) need to delegate storage to an external command instead. This module
+//! defines the `CredentialBackend` trait that both strategies implement, modeled
+//! on Cargo's "credential process" design (RFC 2730): the process is invoked with
+//! a subcommand (`get`, `store`, or `erase`) and exchanges the payload as JSON on
+//! stdin/stdout.
+
+use keyring::{Entry, Error as KeyringError};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::{home_directory, workspace_root_path};
+
+const KEYCHAIN_SERVICE: &str = "ken-desktop";
+
+/// A backend capable of storing, retrieving, and erasing a single secret value
+/// identified by `profile_id`/`token_kind` (e.g. `"access"`, `"refresh"`).
+pub trait CredentialBackend {
+    fn get(&self, profile_id: &str, token_kind: &str) -> Result<Option<String>, String>;
+    fn store(&self, profile_id: &str, token_kind: &str, value: &str) -> Result<(), String>;
+    fn erase(&self, profile_id: &str, token_kind: &str) -> Result<(), String>;
+}
+
+fn account_name(profile_id: &str, token_kind: &str) -> String {
+    format!("{profile_id}:{token_kind}")
+}
+
+fn is_missing_keychain_entry(error: &KeyringError) -> bool {
+    let rendered = error.to_string().to_ascii_lowercase();
+    rendered.contains("no entry") || rendered.contains("item not found")
+}
+
+/// Stores secrets in the OS keychain via the `keyring` crate. This is the
+/// default backend and the one every existing install already uses.
+pub struct KeychainBackend;
+
+impl CredentialBackend for KeychainBackend {
+    fn get(&self, profile_id: &str, token_kind: &str) -> Result<Option<String>, String> {
+        let entry = keychain_entry(profile_id, token_kind)?;
+        match entry.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if is_missing_keychain_entry(&error) => Ok(None),
+            Err(error) => Err(format!("Unable to read {token_kind} token: {error}")),
+        }
+    }
+
+    fn store(&self, profile_id: &str, token_kind: &str, value: &str) -> Result<(), String> {
+        let entry = keychain_entry(profile_id, token_kind)?;
+        entry
+            .set_password(value)
+            .map_err(|error| format!("Unable to save {token_kind} token: {error}"))
+    }
+
+    fn erase(&self, profile_id: &str, token_kind: &str) -> Result<(), String> {
+        let entry = keychain_entry(profile_id, token_kind)?;
+        match entry.delete_password() {
+            Ok(()) => Ok(()),
+            Err(error) if is_missing_keychain_entry(&error) => Ok(()),
+            Err(error) => Err(format!("Unable to clear {token_kind} token: {error}")),
+        }
+    }
+}
+
+fn keychain_entry(profile_id: &str, token_kind: &str) -> Result<Entry, String> {
+    let account = account_name(profile_id, token_kind);
+    Entry::new(KEYCHAIN_SERVICE, &account)
+        .map_err(|error| format!("Unable to access keychain entry: {error}"))
+}
+
+/// Invokes a configured external command for each operation, passing the
+/// profile/token-kind as arguments and the payload as JSON over stdio, per
+/// Cargo's credential-process convention (RFC 2730).
+pub struct ProcessBackend {
+    command: String,
+    args: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessRequest<'a> {
+    profile_id: &'a str,
+    token_kind: &'a str,
+    value: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessResponse {
+    value: Option<String>,
+}
+
+impl ProcessBackend {
+    fn run(&self, subcommand: &str, request: &ProcessRequest<'_>) -> Result<Option<String>, String> {
+        let payload = serde_json::to_vec(request)
+            .map_err(|error| format!("Unable to encode credential-process request: {error}"))?;
+
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .arg(subcommand)
+            .arg(request.profile_id)
+            .arg(request.token_kind)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| format!("Unable to launch credential process '{}': {error}", self.command))?;
+
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| "Credential process stdin unavailable.".to_string())?
+            .write_all(&payload)
+            .map_err(|error| format!("Unable to write to credential process: {error}"))?;
+
+        let output = child
+            .wait_with_output()
+            .map_err(|error| format!("Credential process failed: {error}"))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Credential process '{}' exited with {}: {}",
+                self.command, output.status, stderr
+            ));
+        }
+
+        if output.stdout.iter().all(u8::is_ascii_whitespace) {
+            return Ok(None);
+        }
+
+        let response: ProcessResponse = serde_json::from_slice(&output.stdout)
+            .map_err(|error| format!("Unable to parse credential process response: {error}"))?;
+        Ok(response.value)
+    }
+}
+
+impl CredentialBackend for ProcessBackend {
+    fn get(&self, profile_id: &str, token_kind: &str) -> Result<Option<String>, String> {
+        self.run(
+            "get",
+            &ProcessRequest {
+                profile_id,
+                token_kind,
+                value: None,
+            },
+        )
+    }
+
+    fn store(&self, profile_id: &str, token_kind: &str, value: &str) -> Result<(), String> {
+        self.run(
+            "store",
+            &ProcessRequest {
+                profile_id,
+                token_kind,
+                value: Some(value),
+            },
+        )
+        .map(|_| ())
+    }
+
+    fn erase(&self, profile_id: &str, token_kind: &str) -> Result<(), String> {
+        self.run(
+            "erase",
+            &ProcessRequest {
+                profile_id,
+                token_kind,
+                value: None,
+            },
+        )
+        .map(|_| ())
+    }
+}
+
+/// App-level choice of which `CredentialBackend` to route the auth-token
+/// commands through, read from `credentials.json` in the workspace root.
+#[derive(Deserialize)]
+#[serde(tag = "backend", rename_all = "camelCase")]
+enum CredentialBackendConfig {
+    Keychain,
+    Process { command: String, args: Vec<String> },
+}
+
+impl Default for CredentialBackendConfig {
+    fn default() -> Self {
+        CredentialBackendConfig::Keychain
+    }
+}
+
+fn config_path(workspace_root: &Path) -> std::path::PathBuf {
+    workspace_root.join("credentials.json")
+}
+
+fn load_backend_config() -> CredentialBackendConfig {
+    let Ok(home) = home_directory() else {
+        return CredentialBackendConfig::default();
+    };
+    let path = config_path(&workspace_root_path(&home));
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return CredentialBackendConfig::default();
+    };
+    serde_json::from_str(&contents).unwrap_or_default()
+}
+
+/// Resolves the backend the auth-token commands should route through,
+/// based on the app's persisted credential-backend configuration.
+pub fn resolve_backend() -> Box<dyn CredentialBackend> {
+    match load_backend_config() {
+        CredentialBackendConfig::Keychain => Box::new(KeychainBackend),
+        CredentialBackendConfig::Process { command, args } => {
+            Box::new(ProcessBackend { command, args })
+        }
+    }
+}
+
+/// Whether the resolved backend is the OS keychain. Callers use this to
+/// decide whether a backend error is "keychain unavailable" (safe to fall
+/// back to the encrypted file vault) versus an operator-configured
+/// credential process failing for its own reasons (must be surfaced, not
+/// silently routed around).
+pub fn is_keychain_backend() -> bool {
+    matches!(load_backend_config(), CredentialBackendConfig::Keychain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::account_name;
+
+    #[test]
+    fn account_name_is_stable() {
+        assert_eq!(account_name("thread-1", "access"), "thread-1:access");
+    }
+}
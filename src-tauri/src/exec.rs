@@ -0,0 +1,132 @@
+//! Spawning subprocesses inside a thread's workspace with credentials
+//! injected only into the child's environment — never written to disk, shell
+//! history, or the parent process's own environment (we only ever use
+//! [`std::process::Command::envs`], never `std::env::set_var`).
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const ACCESS_TOKEN_ENV_VAR: &str = "KEN_ACCESS_TOKEN";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecResult {
+    status_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+}
+
+fn resolve_on_path(binary: &str) -> Result<PathBuf, String> {
+    let candidate = PathBuf::from(binary);
+    if candidate.components().count() > 1 {
+        return if candidate.is_file() {
+            Ok(candidate)
+        } else {
+            Err(format!("'{binary}' was not found."))
+        };
+    }
+
+    let path_var = env::var_os("PATH").ok_or_else(|| "PATH is not set.".to_string())?;
+    env::split_paths(&path_var)
+        .map(|dir| dir.join(binary))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| format!("'{binary}' was not found on PATH."))
+}
+
+fn injected_env(profile_id: &str) -> Result<HashMap<String, String>, String> {
+    let access_token = crate::load_token(profile_id, "access")?
+        .ok_or_else(|| "No access token is stored for this profile.".to_string())?;
+
+    let mut env = HashMap::new();
+    env.insert(ACCESS_TOKEN_ENV_VAR.to_string(), access_token);
+    Ok(env)
+}
+
+/// Runs `command` with `args` inside the thread's workspace, with the
+/// profile's access token available to it as `KEN_ACCESS_TOKEN`.
+#[tauri::command]
+pub fn exec_in_workspace(
+    thread_id: String,
+    profile_id: String,
+    command: String,
+    args: Vec<String>,
+) -> Result<ExecResult, String> {
+    let (_, _, thread_path) = crate::resolve_thread_workspace(&thread_id)?;
+    std::fs::create_dir_all(&thread_path)
+        .map_err(|error| format!("Unable to create workspace at {}: {error}", thread_path.display()))?;
+    let binary_path = resolve_on_path(&command)?;
+    let env = injected_env(&profile_id)?;
+
+    let output = Command::new(&binary_path)
+        .args(&args)
+        .current_dir(&thread_path)
+        .envs(&env)
+        .output()
+        .map_err(|error| format!("Unable to launch '{command}': {error}"))?;
+
+    Ok(ExecResult {
+        status_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Opens the platform's default terminal emulator in the thread's workspace,
+/// with the same credential environment as `exec_in_workspace`, for
+/// interactive debugging sessions. On macOS the terminal is launched through
+/// `open -a Terminal`, so that environment is set only on the `open` process
+/// and the shells in the Terminal window do not receive it.
+#[tauri::command]
+pub fn open_terminal_in_workspace(thread_id: String, profile_id: String) -> Result<(), String> {
+    let (_, _, thread_path) = crate::resolve_thread_workspace(&thread_id)?;
+    std::fs::create_dir_all(&thread_path)
+        .map_err(|error| format!("Unable to create workspace at {}: {error}", thread_path.display()))?;
+    let env = injected_env(&profile_id)?;
+
+    default_terminal_command(&thread_path)
+        .envs(&env)
+        .spawn()
+        .map_err(|error| format!("Unable to open a terminal in the workspace: {error}"))?;
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn default_terminal_command(workspace: &Path) -> Command {
+    let mut command = Command::new("open");
+    command.arg("-a").arg("Terminal").arg(workspace);
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn default_terminal_command(workspace: &Path) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/K").current_dir(workspace);
+    command
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn default_terminal_command(workspace: &Path) -> Command {
+    let terminal = env::var("TERMINAL").unwrap_or_else(|_| "x-terminal-emulator".to_string());
+    let mut command = Command::new(terminal);
+    command.current_dir(workspace);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_on_path;
+
+    #[test]
+    fn resolve_on_path_finds_a_common_binary() {
+        assert!(resolve_on_path("sh").is_ok());
+    }
+
+    #[test]
+    fn resolve_on_path_rejects_unknown_binaries() {
+        assert!(resolve_on_path("definitely-not-a-real-binary-name").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Loopback credential-serving server.
+//!
+//! Sibling CLI tools spawned inside a thread's workspace need the profile's
+//! access token without it ever being embedded in argv or an env file on disk.
+//! Instead we run a TCP server bound to `127.0.0.1` on a random port and drop
+//! its port plus a random bearer nonce into a `0600` file inside the thread
+//! workspace. A client proves it knows the nonce, and we independently verify
+//! *who* is actually asking: the PID owning the client side of the TCP
+//! connection is resolved by scanning the OS socket table, that PID's
+//! executable path is looked up, and the result is checked against a
+//! configured allowlist before the token is released. Anything else is
+//! denied and logged.
+
+use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use subtle::ConstantTimeEq;
+use sysinfo::{Pid, System};
+
+const SERVER_INFO_FILE_NAME: &str = ".credential-server";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerInfoFile {
+    port: u16,
+    bearer: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenRequest {
+    bearer: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenResponse {
+    access_token: Option<String>,
+    denied_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialServerInfo {
+    port: u16,
+}
+
+/// Starts the credential server for a thread workspace and returns the port
+/// it bound to. The server runs on a background thread for the lifetime of
+/// the app process; each connection is handled on its own thread.
+#[tauri::command]
+pub fn start_credential_server(
+    thread_id: String,
+    profile_id: String,
+    allowed_executables: Vec<String>,
+) -> Result<CredentialServerInfo, String> {
+    let (_, _, thread_path) = crate::resolve_thread_workspace(&thread_id)?;
+    std::fs::create_dir_all(&thread_path)
+        .map_err(|error| format!("Unable to create workspace at {}: {error}", thread_path.display()))?;
+
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .map_err(|error| format!("Unable to start credential server: {error}"))?;
+    let port = listener
+        .local_addr()
+        .map_err(|error| format!("Unable to read credential server address: {error}"))?
+        .port();
+
+    let mut bearer_bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bearer_bytes);
+    let bearer = hex_encode(&bearer_bytes);
+
+    write_server_info_file(&thread_path, port, &bearer)?;
+
+    let allowlist: Arc<Vec<PathBuf>> = Arc::new(allowed_executables.into_iter().map(PathBuf::from).collect());
+    let profile_id = Arc::new(profile_id);
+    let bearer = Arc::new(bearer);
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let allowlist = Arc::clone(&allowlist);
+            let profile_id = Arc::clone(&profile_id);
+            let bearer = Arc::clone(&bearer);
+            thread::spawn(move || {
+                if let Err(error) = serve_request(stream, &allowlist, &profile_id, &bearer) {
+                    eprintln!("credential server: {error}");
+                }
+            });
+        }
+    });
+
+    Ok(CredentialServerInfo { port })
+}
+
+fn write_server_info_file(thread_path: &Path, port: u16, bearer: &str) -> Result<(), String> {
+    let path = thread_path.join(SERVER_INFO_FILE_NAME);
+    let contents = serde_json::to_vec(&ServerInfoFile {
+        port,
+        bearer: bearer.to_string(),
+    })
+    .map_err(|error| format!("Unable to encode credential server info: {error}"))?;
+    crate::write_restricted_file(&path, &contents)
+}
+
+fn serve_request(
+    mut stream: TcpStream,
+    allowlist: &[PathBuf],
+    profile_id: &str,
+    bearer: &str,
+) -> Result<(), String> {
+    let peer_addr = stream
+        .peer_addr()
+        .map_err(|error| format!("Unable to read peer address: {error}"))?;
+    let local_addr = stream
+        .local_addr()
+        .map_err(|error| format!("Unable to read local address: {error}"))?;
+
+    let mut reader =
+        BufReader::new(stream.try_clone().map_err(|error| format!("Unable to clone connection: {error}"))?);
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|error| format!("Unable to read request: {error}"))?;
+    let request: TokenRequest = serde_json::from_str(line.trim())
+        .map_err(|error| format!("Malformed credential request: {error}"))?;
+
+    let bearer_matches: bool = request.bearer.as_bytes().ct_eq(bearer.as_bytes()).into();
+    let response = if !bearer_matches {
+        eprintln!("credential server: rejected connection from {peer_addr} with an invalid bearer nonce");
+        TokenResponse {
+            access_token: None,
+            denied_reason: Some("Invalid bearer token.".to_string()),
+        }
+    } else {
+        match verify_requesting_process(local_addr.port(), peer_addr, allowlist) {
+            Ok(()) => TokenResponse {
+                access_token: crate::load_token(profile_id, "access")?,
+                denied_reason: None,
+            },
+            Err(reason) => {
+                eprintln!("credential server: denied connection from {peer_addr}: {reason}");
+                TokenResponse {
+                    access_token: None,
+                    denied_reason: Some(reason),
+                }
+            }
+        }
+    };
+
+    let mut payload =
+        serde_json::to_vec(&response).map_err(|error| format!("Unable to encode response: {error}"))?;
+    payload.push(b'\n');
+    stream
+        .write_all(&payload)
+        .map_err(|error| format!("Unable to write response: {error}"))
+}
+
+/// Identifies the process on the other end of `peer_addr` by scanning the OS
+/// socket table for the TCP connection whose remote port is our own
+/// `server_port` and whose local port is the peer's port, then checks that
+/// process's executable path against the allowlist.
+fn verify_requesting_process(
+    server_port: u16,
+    peer_addr: SocketAddr,
+    allowlist: &[PathBuf],
+) -> Result<(), String> {
+    let address_family_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
+    let protocol_flags = ProtocolFlags::TCP;
+    let sockets = get_sockets_info(address_family_flags, protocol_flags)
+        .map_err(|error| format!("Unable to read socket table: {error}"))?;
+
+    let pid = sockets
+        .into_iter()
+        .find_map(|socket_info| match socket_info.protocol_socket_info {
+            ProtocolSocketInfo::Tcp(tcp)
+                if tcp.local_port == peer_addr.port() && tcp.remote_port == server_port =>
+            {
+                socket_info.associated_pids.first().copied()
+            }
+            _ => None,
+        })
+        .ok_or_else(|| "Unable to identify the requesting process from the socket table.".to_string())?;
+
+    let mut system = System::new_all();
+    system.refresh_all();
+    let process = system
+        .process(Pid::from_u32(pid))
+        .ok_or_else(|| format!("Process {pid} no longer exists."))?;
+    let exe_path = process
+        .exe()
+        .ok_or_else(|| format!("Unable to resolve the executable for process {pid}."))?;
+
+    if allowlist.iter().any(|allowed| allowed == exe_path) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Process {pid} ({}) is not in the credential allowlist.",
+            exe_path.display()
+        ))
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hex_encode;
+
+    #[test]
+    fn hex_encode_renders_lowercase_pairs() {
+        assert_eq!(hex_encode(&[0x0f, 0xa2, 0x00]), "0fa200");
+    }
+}
@@ -1,15 +1,36 @@
-use keyring::{Entry, Error as KeyringError};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-const KEYCHAIN_SERVICE: &str = "ken-desktop";
+mod credentials;
+mod exec;
+mod ipc;
+mod paseto;
+mod vault;
+
+use credentials::CredentialBackend;
+use vault::FileVaultBackend;
+
+/// Tokens are treated as expired once this many seconds or fewer remain,
+/// so callers refresh a little before the backend actually rejects them.
+const DEFAULT_EXPIRY_SKEW_SECS: i64 = 60;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AuthTokens {
     access_token: String,
     refresh_token: Option<String>,
+    expires_at: Option<i64>,
+    expired: bool,
+    expires_in_secs: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenStatus {
+    expired: bool,
+    expires_in_secs: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -21,10 +42,6 @@ struct ThreadWorkspaceInfo {
     created: bool,
 }
 
-fn account_name(profile_id: &str, token_kind: &str) -> String {
-    format!("{profile_id}:{token_kind}")
-}
-
 fn normalize_token(token: &str) -> Option<String> {
     let trimmed = token.trim();
     if trimmed.is_empty() {
@@ -52,16 +69,58 @@ fn normalize_thread_id(thread_id: &str) -> Option<String> {
     }
 }
 
-fn home_directory() -> Result<PathBuf, String> {
+pub(crate) fn home_directory() -> Result<PathBuf, String> {
     std::env::var_os("HOME")
         .map(PathBuf::from)
         .ok_or_else(|| "Unable to resolve user home directory.".to_string())
 }
 
-fn workspace_root_path(home_directory: &Path) -> PathBuf {
+pub(crate) fn workspace_root_path(home_directory: &Path) -> PathBuf {
     home_directory.join("Executive Assistant").join("Ken")
 }
 
+/// Writes `contents` to `path`, created with owner-only (`0600`) permissions
+/// from the moment the file exists — not chmod'd after the fact, which would
+/// leave a window where the file sits at the process's default umask (often
+/// world-readable) before being locked down.
+pub(crate) fn write_restricted_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::fs::OpenOptions;
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|error| format!("Unable to write {}: {error}", path.display()))?;
+        file.write_all(contents)
+            .map_err(|error| format!("Unable to write {}: {error}", path.display()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::write(path, contents).map_err(|error| format!("Unable to write {}: {error}", path.display()))
+    }
+}
+
+fn expiry_status(expires_at: Option<i64>, skew_secs: i64) -> (bool, Option<i64>) {
+    let Some(expires_at) = expires_at else {
+        return (false, None);
+    };
+
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+    let expires_in_secs = expires_at - now_secs;
+
+    (expires_in_secs <= skew_secs, Some(expires_in_secs))
+}
+
 fn workspace_create_error(path: &Path, error: std::io::Error) -> String {
     let base = format!("Unable to create workspace at {}: {error}", path.display());
     if error.kind() == std::io::ErrorKind::PermissionDenied {
@@ -71,22 +130,48 @@ fn workspace_create_error(path: &Path, error: std::io::Error) -> String {
     }
 }
 
-fn keychain_entry(profile_id: &str, token_kind: &str) -> Result<Entry, String> {
-    let account = account_name(profile_id, token_kind);
-    Entry::new(KEYCHAIN_SERVICE, &account)
-        .map_err(|error| format!("Unable to access keychain entry: {error}"))
+/// Stores a token via the configured backend, falling back to the encrypted
+/// file vault only when that backend is the OS keychain and the keychain
+/// itself errored out. An operator-configured credential process failing is
+/// surfaced as-is rather than silently duplicated into the vault.
+fn store_token(profile_id: &str, token_kind: &str, value: &str) -> Result<(), String> {
+    let backend = credentials::resolve_backend();
+    match backend.store(profile_id, token_kind, value) {
+        Ok(()) => Ok(()),
+        Err(primary_error) if credentials::is_keychain_backend() => FileVaultBackend
+            .store(profile_id, token_kind, value)
+            .map_err(|vault_error| format!("{primary_error}; vault fallback also failed: {vault_error}")),
+        Err(primary_error) => Err(primary_error),
+    }
 }
 
-fn is_missing_keychain_entry(error: &KeyringError) -> bool {
-    let rendered = error.to_string().to_ascii_lowercase();
-    rendered.contains("no entry") || rendered.contains("item not found")
+/// Reads a token via the configured backend, falling back to the encrypted
+/// file vault only when that backend is the OS keychain and the keychain
+/// itself errored out. An operator-configured credential process failing is
+/// surfaced as-is rather than silently read from the vault instead.
+pub(crate) fn load_token(profile_id: &str, token_kind: &str) -> Result<Option<String>, String> {
+    let backend = credentials::resolve_backend();
+    match backend.get(profile_id, token_kind) {
+        Ok(value) => Ok(value),
+        Err(primary_error) if credentials::is_keychain_backend() => FileVaultBackend
+            .get(profile_id, token_kind)
+            .map_err(|vault_error| format!("{primary_error}; vault fallback also failed: {vault_error}")),
+        Err(primary_error) => Err(primary_error),
+    }
 }
 
-fn delete_if_present(entry: &Entry) -> Result<(), String> {
-    match entry.delete_password() {
+/// Erases a token via the configured backend, falling back to the encrypted
+/// file vault only when that backend is the OS keychain and the keychain
+/// itself errored out. An operator-configured credential process failing is
+/// surfaced as-is rather than silently erased from the vault instead.
+fn erase_token(profile_id: &str, token_kind: &str) -> Result<(), String> {
+    let backend = credentials::resolve_backend();
+    match backend.erase(profile_id, token_kind) {
         Ok(()) => Ok(()),
-        Err(error) if is_missing_keychain_entry(&error) => Ok(()),
-        Err(error) => Err(format!("Unable to clear keychain entry: {error}")),
+        Err(primary_error) if credentials::is_keychain_backend() => FileVaultBackend
+            .erase(profile_id, token_kind)
+            .map_err(|vault_error| format!("{primary_error}; vault fallback also failed: {vault_error}")),
+        Err(primary_error) => Err(primary_error),
     }
 }
 
@@ -95,71 +180,91 @@ fn save_auth_tokens(
     profile_id: String,
     access_token: String,
     refresh_token: Option<String>,
+    expires_at: Option<i64>,
 ) -> Result<(), String> {
     let normalized_access_token =
         normalize_token(&access_token).ok_or_else(|| "Access token is required.".to_string())?;
 
-    let access_entry = keychain_entry(&profile_id, "access")?;
-    access_entry
-        .set_password(&normalized_access_token)
-        .map_err(|error| format!("Unable to save access token: {error}"))?;
+    store_token(&profile_id, "access", &normalized_access_token)?;
 
-    let refresh_entry = keychain_entry(&profile_id, "refresh")?;
-    match refresh_token {
-        Some(value) => match normalize_token(&value) {
-            Some(normalized_refresh_token) => refresh_entry
-                .set_password(&normalized_refresh_token)
-                .map_err(|error| format!("Unable to save refresh token: {error}"))?,
-            None => delete_if_present(&refresh_entry)?,
-        },
-        None => delete_if_present(&refresh_entry)?,
+    match refresh_token.as_deref().and_then(normalize_token) {
+        Some(normalized_refresh_token) => {
+            store_token(&profile_id, "refresh", &normalized_refresh_token)?
+        }
+        None => erase_token(&profile_id, "refresh")?,
+    }
+
+    match expires_at {
+        Some(value) => store_token(&profile_id, "expiry", &value.to_string())?,
+        None => erase_token(&profile_id, "expiry")?,
     }
 
     Ok(())
 }
 
 #[tauri::command]
-fn load_auth_tokens(profile_id: String) -> Result<Option<AuthTokens>, String> {
-    let access_entry = keychain_entry(&profile_id, "access")?;
-    let access_token = match access_entry.get_password() {
-        Ok(value) => value,
-        Err(error) if is_missing_keychain_entry(&error) => return Ok(None),
-        Err(error) => return Err(format!("Unable to read access token: {error}")),
-    };
-
-    let refresh_entry = keychain_entry(&profile_id, "refresh")?;
-    let refresh_token = match refresh_entry.get_password() {
-        Ok(value) => Some(value),
-        Err(error) if is_missing_keychain_entry(&error) => None,
-        Err(error) => return Err(format!("Unable to read refresh token: {error}")),
+fn load_auth_tokens(
+    profile_id: String,
+    skew_secs: Option<i64>,
+) -> Result<Option<AuthTokens>, String> {
+    let access_token = match load_token(&profile_id, "access")? {
+        Some(value) => value,
+        None => return Ok(None),
     };
+    let refresh_token = load_token(&profile_id, "refresh")?;
+    let expires_at = load_token(&profile_id, "expiry")?.and_then(|value| value.parse().ok());
+    let (expired, expires_in_secs) =
+        expiry_status(expires_at, skew_secs.unwrap_or(DEFAULT_EXPIRY_SKEW_SECS));
 
     Ok(Some(AuthTokens {
         access_token,
         refresh_token,
+        expires_at,
+        expired,
+        expires_in_secs,
     }))
 }
 
 #[tauri::command]
 fn clear_auth_tokens(profile_id: String) -> Result<(), String> {
-    let access_entry = keychain_entry(&profile_id, "access")?;
-    delete_if_present(&access_entry)?;
-
-    let refresh_entry = keychain_entry(&profile_id, "refresh")?;
-    delete_if_present(&refresh_entry)?;
-
+    erase_token(&profile_id, "access")?;
+    erase_token(&profile_id, "refresh")?;
+    erase_token(&profile_id, "expiry")?;
     Ok(())
 }
 
+/// Returns just the expiry validity for a profile's access token, without
+/// touching the secret value itself, so UI polling never reads it unnecessarily.
 #[tauri::command]
-fn ensure_thread_workspace(thread_id: String) -> Result<ThreadWorkspaceInfo, String> {
-    let normalized_thread_id = normalize_thread_id(&thread_id).ok_or_else(|| {
+fn token_status(profile_id: String, skew_secs: Option<i64>) -> Result<TokenStatus, String> {
+    let expires_at = load_token(&profile_id, "expiry")?.and_then(|value| value.parse().ok());
+    let (expired, expires_in_secs) =
+        expiry_status(expires_at, skew_secs.unwrap_or(DEFAULT_EXPIRY_SKEW_SECS));
+
+    Ok(TokenStatus {
+        expired,
+        expires_in_secs,
+    })
+}
+
+/// Resolves (and validates) the on-disk paths for a thread's workspace
+/// without creating it, so callers that just need the path (the IPC
+/// credential server, `exec_in_workspace`) don't duplicate this logic.
+pub(crate) fn resolve_thread_workspace(thread_id: &str) -> Result<(String, PathBuf, PathBuf), String> {
+    let normalized_thread_id = normalize_thread_id(thread_id).ok_or_else(|| {
         "Thread ID is required and can only contain letters, numbers, '.', '_' or '-'.".to_string()
     })?;
 
     let home = home_directory()?;
     let root_path = workspace_root_path(&home);
     let thread_path = root_path.join(&normalized_thread_id);
+
+    Ok((normalized_thread_id, root_path, thread_path))
+}
+
+#[tauri::command]
+fn ensure_thread_workspace(thread_id: String) -> Result<ThreadWorkspaceInfo, String> {
+    let (normalized_thread_id, root_path, thread_path) = resolve_thread_workspace(&thread_id)?;
     let already_exists = thread_path.exists();
 
     fs::create_dir_all(&thread_path)
@@ -180,7 +285,15 @@ pub fn run() {
             save_auth_tokens,
             load_auth_tokens,
             clear_auth_tokens,
-            ensure_thread_workspace
+            token_status,
+            ensure_thread_workspace,
+            vault::set_vault_passphrase,
+            ipc::start_credential_server,
+            paseto::mint_session_token,
+            paseto::verify_session_token,
+            paseto::rotate_signing_key,
+            exec::exec_in_workspace,
+            exec::open_terminal_in_workspace
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -189,14 +302,42 @@ pub fn run() {
 #[cfg(test)]
 mod tests {
     use super::{
-        account_name, normalize_thread_id, normalize_token, workspace_create_error,
+        expiry_status, normalize_thread_id, normalize_token, workspace_create_error,
         workspace_root_path,
     };
     use std::path::Path;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn now_secs() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock should be after the epoch")
+            .as_secs() as i64
+    }
+
+    #[test]
+    fn expiry_status_treats_missing_expiry_as_not_expired() {
+        assert_eq!(expiry_status(None, 60), (false, None));
+    }
+
+    #[test]
+    fn expiry_status_reports_time_remaining_when_far_from_expiring() {
+        let (expired, expires_in_secs) = expiry_status(Some(now_secs() + 3600), 60);
+        assert!(!expired);
+        assert!(expires_in_secs.expect("expiry should be present") > 3000);
+    }
+
+    #[test]
+    fn expiry_status_treats_near_expiry_within_skew_as_expired() {
+        let (expired, _) = expiry_status(Some(now_secs() + 30), 60);
+        assert!(expired);
+    }
 
     #[test]
-    fn account_name_is_stable() {
-        assert_eq!(account_name("thread-1", "access"), "thread-1:access");
+    fn expiry_status_treats_past_expiry_as_expired() {
+        let (expired, expires_in_secs) = expiry_status(Some(now_secs() - 10), 60);
+        assert!(expired);
+        assert!(expires_in_secs.expect("expiry should be present") < 0);
     }
 
     #[test]
@@ -0,0 +1,281 @@
+//! Local PASETO v4.public signing for backend authentication.
+//!
+//! Instead of sending the long-lived OAuth bearer token to the backend on
+//! every request, the app mints short-lived, locally-signed PASETO tokens.
+//! An Ed25519 keypair is generated on first use and the secret half is kept
+//! in the keychain (via [`crate::store_token`]/[`crate::load_token`], PASERK
+//! (`k4.secret`/`k4.public`) encoded). `rotate_signing_key` replaces the
+//! keypair but keeps the previous public key around for a grace window, so
+//! tokens signed just before a rotation still verify.
+
+use pasetors::claims::{Claims, ClaimsValidationRules};
+use pasetors::keys::{AsymmetricKeyPair, AsymmetricPublicKey, AsymmetricSecretKey, Generate};
+use pasetors::paserk::FormatAsPaserk;
+use pasetors::public;
+use pasetors::token::UntrustedToken;
+use pasetors::version4::V4;
+use pasetors::Public;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+const SIGNING_PROFILE: &str = "paseto-signing-key";
+const SECRET_KIND: &str = "secret";
+const PUBLIC_KIND: &str = "public";
+const PREVIOUS_PUBLIC_KIND: &str = "previous-public";
+const PREVIOUS_PUBLIC_EXPIRY_KIND: &str = "previous-public-expiry";
+
+/// How long a rotated-out public key still verifies in-flight tokens.
+const ROTATION_GRACE_SECS: i64 = 24 * 60 * 60;
+
+/// Registered claim names: the ones the app sets itself (`sub`, `iat`, `exp`,
+/// and `nbf` via `Claims::new`) plus `iss`, `aud` and `jti`. Callers of
+/// `mint_session_token` may not set any of them via their own additional claims.
+const RESERVED_CLAIM_KEYS: &[&str] = &["sub", "iat", "exp", "iss", "aud", "nbf", "jti"];
+
+/// Serializes keypair bootstrap/rotation so two concurrent first-run calls
+/// (e.g. two `mint_session_token` calls racing each other) can't each
+/// generate and persist their own keypair, silently overwriting the loser's.
+static SIGNING_KEY_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_signing_key() -> std::sync::MutexGuard<'static, ()> {
+    SIGNING_KEY_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn parse_secret_key(paserk: &str) -> Result<AsymmetricSecretKey<V4>, String> {
+    AsymmetricSecretKey::<V4>::try_from(paserk)
+        .map_err(|error| format!("Unable to parse stored signing secret key: {error}"))
+}
+
+fn parse_public_key(paserk: &str) -> Result<AsymmetricPublicKey<V4>, String> {
+    AsymmetricPublicKey::<V4>::try_from(paserk)
+        .map_err(|error| format!("Unable to parse stored signing public key: {error}"))
+}
+
+fn persist_keypair(keypair: &AsymmetricKeyPair<V4>) -> Result<(), String> {
+    let mut secret_paserk = String::new();
+    keypair
+        .secret
+        .fmt(&mut secret_paserk)
+        .map_err(|error| format!("Unable to encode signing secret key: {error}"))?;
+    let mut public_paserk = String::new();
+    keypair
+        .public
+        .fmt(&mut public_paserk)
+        .map_err(|error| format!("Unable to encode signing public key: {error}"))?;
+
+    crate::store_token(SIGNING_PROFILE, SECRET_KIND, &secret_paserk)?;
+    crate::store_token(SIGNING_PROFILE, PUBLIC_KIND, &public_paserk)?;
+    Ok(())
+}
+
+fn ensure_signing_keypair() -> Result<AsymmetricKeyPair<V4>, String> {
+    let _lock = lock_signing_key();
+    let stored = (
+        crate::load_token(SIGNING_PROFILE, SECRET_KIND)?,
+        crate::load_token(SIGNING_PROFILE, PUBLIC_KIND)?,
+    );
+
+    match stored {
+        (Some(secret_paserk), Some(public_paserk)) => Ok(AsymmetricKeyPair {
+            secret: parse_secret_key(&secret_paserk)?,
+            public: parse_public_key(&public_paserk)?,
+        }),
+        _ => {
+            let keypair = AsymmetricKeyPair::<V4>::generate()
+                .map_err(|error| format!("Unable to generate signing keypair: {error}"))?;
+            persist_keypair(&keypair)?;
+            Ok(keypair)
+        }
+    }
+}
+
+/// The current signing public key, plus the previous one if it's still
+/// inside its rotation grace window. Read-only: unlike minting, verification
+/// never bootstraps a keypair — a token can't possibly be valid against a
+/// key that was just now generated, so there's nothing to gain by generating
+/// one here, and doing so would mutate the stored signing key as a side
+/// effect of a read path.
+fn active_verification_keys() -> Result<Vec<AsymmetricPublicKey<V4>>, String> {
+    let mut keys = Vec::new();
+
+    match crate::load_token(SIGNING_PROFILE, PUBLIC_KIND)? {
+        Some(current) => keys.push(parse_public_key(&current)?),
+        None => return Err("No signing keys are configured.".to_string()),
+    }
+
+    if let Some(expiry) = crate::load_token(SIGNING_PROFILE, PREVIOUS_PUBLIC_EXPIRY_KIND)? {
+        if is_within_grace_window(&expiry, OffsetDateTime::now_utc().unix_timestamp()) {
+            if let Some(previous) = crate::load_token(SIGNING_PROFILE, PREVIOUS_PUBLIC_KIND)? {
+                keys.push(parse_public_key(&previous)?);
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Whether a stored previous-key expiry (Unix seconds, as text) is still in
+/// the future relative to `now_secs`. An unparseable expiry is treated as
+/// already elapsed, not as "always valid".
+fn is_within_grace_window(expiry_raw: &str, now_secs: i64) -> bool {
+    expiry_raw
+        .parse::<i64>()
+        .map(|expiry_secs| now_secs < expiry_secs)
+        .unwrap_or(false)
+}
+
+fn rfc3339(instant: OffsetDateTime) -> Result<String, String> {
+    instant
+        .format(&Rfc3339)
+        .map_err(|error| format!("Unable to format timestamp: {error}"))
+}
+
+/// Rejects any caller-supplied additional claim whose key is one of
+/// [`RESERVED_CLAIM_KEYS`], so a caller can't silently extend or override a
+/// minted token's computed expiry (or subject, issuer, etc.).
+fn reject_reserved_claims(claims: &HashMap<String, Value>) -> Result<(), String> {
+    for key in claims.keys() {
+        if RESERVED_CLAIM_KEYS.contains(&key.as_str()) {
+            return Err(format!("Claim '{key}' is reserved and cannot be set directly."));
+        }
+    }
+    Ok(())
+}
+
+/// Mints a signed, short-lived PASETO (`v4.public`) carrying `sub`, `iat`,
+/// `exp`, and any additional claims the caller supplies.
+#[tauri::command]
+pub fn mint_session_token(
+    profile_id: String,
+    claims: HashMap<String, Value>,
+    ttl_secs: i64,
+) -> Result<String, String> {
+    reject_reserved_claims(&claims)?;
+    let keypair = ensure_signing_keypair()?;
+
+    let mut token_claims =
+        Claims::new().map_err(|error| format!("Unable to initialize session claims: {error}"))?;
+    token_claims
+        .subject(&profile_id)
+        .map_err(|error| format!("Unable to set subject claim: {error}"))?;
+    token_claims
+        .issued_at(&rfc3339(OffsetDateTime::now_utc())?)
+        .map_err(|error| format!("Unable to set issued-at claim: {error}"))?;
+    token_claims
+        .expiration(&rfc3339(OffsetDateTime::now_utc() + Duration::seconds(ttl_secs))?)
+        .map_err(|error| format!("Unable to set expiration claim: {error}"))?;
+
+    for (key, value) in claims {
+        token_claims
+            .add_additional(&key, value)
+            .map_err(|error| format!("Unable to set claim '{key}': {error}"))?;
+    }
+
+    public::sign(&keypair.secret, &token_claims, None, None)
+        .map_err(|error| format!("Unable to sign session token: {error}"))
+}
+
+/// Verifies a PASETO minted by this app (or, during a rotation grace window,
+/// the previous signing key) and returns its claims.
+#[tauri::command]
+pub fn verify_session_token(token: String) -> Result<HashMap<String, Value>, String> {
+    let validation_rules = ClaimsValidationRules::new();
+    let untrusted_token = UntrustedToken::<Public, V4>::try_from(&token)
+        .map_err(|error| format!("Malformed session token: {error}"))?;
+
+    let mut last_error = "No signing keys are configured.".to_string();
+    for public_key in active_verification_keys()? {
+        match public::verify(&public_key, &untrusted_token, &validation_rules, None, None) {
+            Ok(trusted_token) => {
+                let claims = trusted_token
+                    .payload_claims()
+                    .ok_or_else(|| "Session token carries no claims.".to_string())?;
+                let encoded = serde_json::to_value(claims)
+                    .map_err(|error| format!("Unable to read session token claims: {error}"))?;
+                return serde_json::from_value(encoded)
+                    .map_err(|error| format!("Unable to read session token claims: {error}"));
+            }
+            Err(error) => last_error = format!("Session token failed verification: {error}"),
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Generates a fresh signing keypair, keeping the previous public key around
+/// for [`ROTATION_GRACE_SECS`] so tokens already in flight still verify.
+#[tauri::command]
+pub fn rotate_signing_key() -> Result<(), String> {
+    let _lock = lock_signing_key();
+    if let Some(current_public) = crate::load_token(SIGNING_PROFILE, PUBLIC_KIND)? {
+        crate::store_token(SIGNING_PROFILE, PREVIOUS_PUBLIC_KIND, &current_public)?;
+        let expiry = OffsetDateTime::now_utc().unix_timestamp() + ROTATION_GRACE_SECS;
+        crate::store_token(
+            SIGNING_PROFILE,
+            PREVIOUS_PUBLIC_EXPIRY_KIND,
+            &expiry.to_string(),
+        )?;
+    }
+
+    let keypair = AsymmetricKeyPair::<V4>::generate()
+        .map_err(|error| format!("Unable to generate signing keypair: {error}"))?;
+    persist_keypair(&keypair)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_within_grace_window, reject_reserved_claims, rfc3339};
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn rfc3339_formats_the_unix_epoch() {
+        assert_eq!(
+            rfc3339(OffsetDateTime::UNIX_EPOCH).expect("should format"),
+            "1970-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn is_within_grace_window_accepts_future_expiry() {
+        assert!(is_within_grace_window("1000", 500));
+    }
+
+    #[test]
+    fn is_within_grace_window_rejects_past_expiry() {
+        assert!(!is_within_grace_window("500", 1000));
+    }
+
+    #[test]
+    fn is_within_grace_window_rejects_unparseable_expiry() {
+        assert!(!is_within_grace_window("not-a-number", 0));
+    }
+
+    #[test]
+    fn reject_reserved_claims_allows_custom_claims() {
+        let mut claims = HashMap::new();
+        claims.insert("role".to_string(), Value::String("admin".to_string()));
+        assert!(reject_reserved_claims(&claims).is_ok());
+    }
+
+    #[test]
+    fn reject_reserved_claims_rejects_exp_override() {
+        let mut claims = HashMap::new();
+        claims.insert(
+            "exp".to_string(),
+            Value::String("2999-01-01T00:00:00Z".to_string()),
+        );
+        assert!(reject_reserved_claims(&claims).is_err());
+    }
+
+    #[test]
+    fn reject_reserved_claims_rejects_sub_override() {
+        let mut claims = HashMap::new();
+        claims.insert("sub".to_string(), Value::String("someone-else".to_string()));
+        assert!(reject_reserved_claims(&claims).is_err());
+    }
+}
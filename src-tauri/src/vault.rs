@@ -0,0 +1,171 @@
+//! Encrypted file-vault fallback for when the OS keychain is unavailable
+//! (headless Linux with no Secret Service daemon, locked-down environments).
+//!
+//! Tokens are sealed into a single file at `~/Executive Assistant/Ken/.vault`.
+//! The on-disk format is `salt || nonce || ciphertext`: a random 16-byte salt
+//! feeds Argon2id to derive a 32-byte key from the user's passphrase, and a
+//! fresh random 24-byte `XNonce` is used to seal the contents with
+//! ChaCha20-Poly1305 on every write. Reads re-derive the key and verify the
+//! AEAD tag, so a tampered or wrong-passphrase file is rejected rather than
+//! silently returning garbage.
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::credentials::CredentialBackend;
+use crate::{home_directory, workspace_root_path};
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const VAULT_FILE_NAME: &str = ".vault";
+
+type VaultContents = HashMap<String, String>;
+
+static VAULT_PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Serializes the vault file's read-modify-write cycle. Tauri dispatches
+/// commands on a thread pool, so two concurrent `store`/`erase` calls could
+/// otherwise both read the same contents and silently clobber each other's
+/// write.
+static VAULT_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_vault_file() -> std::sync::MutexGuard<'static, ()> {
+    VAULT_FILE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Sets the passphrase used to derive the vault's encryption key. Held only
+/// in memory for this process's lifetime; it is never written to disk.
+#[tauri::command]
+pub fn set_vault_passphrase(passphrase: String) -> Result<(), String> {
+    let mut guard = VAULT_PASSPHRASE
+        .lock()
+        .map_err(|_| "Vault passphrase lock was poisoned.".to_string())?;
+    *guard = Some(passphrase);
+    Ok(())
+}
+
+fn current_passphrase() -> Result<String, String> {
+    VAULT_PASSPHRASE
+        .lock()
+        .map_err(|_| "Vault passphrase lock was poisoned.".to_string())?
+        .clone()
+        .ok_or_else(|| "Vault passphrase has not been set.".to_string())
+}
+
+fn vault_path() -> Result<PathBuf, String> {
+    let home = home_directory()?;
+    Ok(workspace_root_path(&home).join(VAULT_FILE_NAME))
+}
+
+fn account_key(profile_id: &str, token_kind: &str) -> String {
+    format!("{profile_id}:{token_kind}")
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+    let mut key = [0u8; KEY_LEN];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|error| format!("Unable to derive vault key: {error}"))?;
+    Ok(key)
+}
+
+fn read_vault() -> Result<VaultContents, String> {
+    let path = vault_path()?;
+    let raw = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(VaultContents::new())
+        }
+        Err(error) => return Err(format!("Unable to read vault at {}: {error}", path.display())),
+    };
+
+    if raw.len() < SALT_LEN + NONCE_LEN {
+        return Err("Vault file is corrupt.".to_string());
+    }
+    let (salt, rest) = raw.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let passphrase = current_passphrase()?;
+    let key = derive_key(&passphrase, salt)?;
+    let cipher = XChaCha20Poly1305::new(key.as_ref().into());
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Unable to unlock vault: wrong passphrase or the file was tampered with.".to_string())?;
+
+    serde_json::from_slice(&plaintext)
+        .map_err(|error| format!("Vault contents are corrupt: {error}"))
+}
+
+fn write_vault(contents: &VaultContents) -> Result<(), String> {
+    let home = home_directory()?;
+    let root = workspace_root_path(&home);
+    std::fs::create_dir_all(&root)
+        .map_err(|error| format!("Unable to create workspace at {}: {error}", root.display()))?;
+
+    let passphrase = current_passphrase()?;
+
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(&passphrase, &salt)?;
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+    let cipher = XChaCha20Poly1305::new(key.as_ref().into());
+    let plaintext = serde_json::to_vec(contents)
+        .map_err(|error| format!("Unable to encode vault contents: {error}"))?;
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce_bytes), plaintext.as_ref())
+        .map_err(|error| format!("Unable to seal vault: {error}"))?;
+
+    let mut payload = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    payload.extend_from_slice(&salt);
+    payload.extend_from_slice(&nonce_bytes);
+    payload.extend_from_slice(&ciphertext);
+
+    let path = vault_path()?;
+    crate::write_restricted_file(&path, &payload)
+}
+
+/// Stores tokens in the encrypted vault file rather than the OS keychain.
+pub struct FileVaultBackend;
+
+impl CredentialBackend for FileVaultBackend {
+    fn get(&self, profile_id: &str, token_kind: &str) -> Result<Option<String>, String> {
+        let _lock = lock_vault_file();
+        let contents = read_vault()?;
+        Ok(contents.get(&account_key(profile_id, token_kind)).cloned())
+    }
+
+    fn store(&self, profile_id: &str, token_kind: &str, value: &str) -> Result<(), String> {
+        let _lock = lock_vault_file();
+        let mut contents = read_vault()?;
+        contents.insert(account_key(profile_id, token_kind), value.to_string());
+        write_vault(&contents)
+    }
+
+    fn erase(&self, profile_id: &str, token_kind: &str) -> Result<(), String> {
+        let _lock = lock_vault_file();
+        let mut contents = read_vault()?;
+        if contents.remove(&account_key(profile_id, token_kind)).is_some() {
+            write_vault(&contents)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::account_key;
+
+    #[test]
+    fn account_key_is_stable() {
+        assert_eq!(account_key("thread-1", "access"), "thread-1:access");
+    }
+}